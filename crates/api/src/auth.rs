@@ -0,0 +1,416 @@
+//! Authentication
+//!
+//! Users register and log in with an email and password. Passwords are hashed
+//! with Argon2 and a successful login returns a signed JWT access token. Handlers
+//! that take a [`CurrentUser`] argument only run for requests carrying a valid
+//! `Authorization: Bearer <token>` header.
+use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use argon2::password_hash::{
+    rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
+};
+use argon2::Argon2;
+use axum::{
+    extract::{FromRef, FromRequestParts, State},
+    http::{header::AUTHORIZATION, request::Parts, HeaderMap, StatusCode},
+    routing::{get, post},
+    Router,
+};
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqlitePool;
+
+use crate::extract::Json;
+use crate::prelude::*;
+use crate::state::AppState;
+
+/// Shortest accepted signing secret; HS256 keys should be at least as long as the hash output.
+pub const MIN_SECRET_LEN: usize = 32;
+
+/// Hash checked when no usable one is stored for an email, so a failed login takes
+/// as long whether or not the email is registered.
+static DUMMY_HASH: LazyLock<String> =
+    LazyLock::new(|| hash_password("dummy password").expect("hashing a constant password"));
+
+/// Keys used to sign and verify access tokens.
+#[derive(Clone)]
+pub struct JwtKeys {
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    ttl: u64,
+}
+
+impl JwtKeys {
+    /// Create HS256 keys from a shared secret, issuing tokens valid for `ttl` seconds.
+    ///
+    /// Secrets shorter than [`MIN_SECRET_LEN`] bytes are rejected.
+    pub fn new(secret: &str, ttl: u64) -> std::result::Result<Self, Error> {
+        if secret.len() < MIN_SECRET_LEN {
+            return Err(Error::WeakJwtSecret);
+        }
+
+        Ok(Self {
+            encoding: EncodingKey::from_secret(secret.as_bytes()),
+            decoding: DecodingKey::from_secret(secret.as_bytes()),
+            ttl,
+        })
+    }
+
+    pub(crate) fn issue(&self, user_id: i64) -> std::result::Result<TokenResponse, Error> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let claims = Claims {
+            sub: user_id.to_string(),
+            iat: now,
+            exp: now + self.ttl,
+        };
+
+        Ok(TokenResponse {
+            access_token: jsonwebtoken::encode(&Header::default(), &claims, &self.encoding)?,
+            token_type: "Bearer",
+            expires_in: self.ttl,
+        })
+    }
+
+    pub(crate) fn verify(&self, token: &str) -> std::result::Result<Claims, Error> {
+        jsonwebtoken::decode::<Claims>(token, &self.decoding, &Validation::default())
+            .map(|data| data.claims)
+            .map_err(|_| Error::Unauthorized)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct Claims {
+    pub(crate) sub: String,
+    iat: u64,
+    exp: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenResponse {
+    pub(crate) access_token: String,
+    token_type: &'static str,
+    expires_in: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterRequest {
+    name: String,
+    email: String,
+    password: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LoginRequest {
+    email: String,
+    password: String,
+}
+
+/// The authenticated user making the request.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct CurrentUser {
+    pub id: i64,
+    pub name: String,
+    pub email: String,
+}
+
+impl<S> FromRequestParts<S> for CurrentUser
+where
+    JwtKeys: FromRef<S>,
+    SqlitePool: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> std::result::Result<Self, Self::Rejection> {
+        let token = bearer_token(&parts.headers).ok_or(Error::Unauthorized)?;
+        let claims = JwtKeys::from_ref(state).verify(token)?;
+        let id: i64 = claims.sub.parse().map_err(|_| Error::Unauthorized)?;
+
+        sqlx::query_as("SELECT id, name, email FROM users WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&SqlitePool::from_ref(state))
+            .await?
+            .ok_or(Error::Unauthorized)
+    }
+}
+
+/// Extract the token from an `Authorization: Bearer <token>` header.
+pub(crate) fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
+
+/// Routes under `/auth`.
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/auth/register", post(register))
+        .route("/auth/login", post(login))
+        .route("/auth/me", get(me))
+}
+
+async fn register(
+    State(state): State<AppState>,
+    Json(request): Json<RegisterRequest>,
+) -> std::result::Result<(StatusCode, Json<TokenResponse>), Error> {
+    let email = normalize_email(&request.email);
+    if email.is_empty() || request.password.is_empty() {
+        return Err(Error::InvalidRequest("email and password are required"));
+    }
+
+    let password = request.password;
+    let password_hash = blocking(move || hash_password(&password)).await??;
+
+    let id: i64 = sqlx::query_scalar(
+        "INSERT INTO users (name, email, password_hash) VALUES (?, ?, ?) RETURNING id",
+    )
+    .bind(&request.name)
+    .bind(&email)
+    .bind(&password_hash)
+    .fetch_one(&state.pool)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => Error::EmailTaken,
+        e => Error::Sqlx(e),
+    })?;
+
+    Ok((StatusCode::CREATED, Json(state.jwt.issue(id)?)))
+}
+
+async fn login(
+    State(state): State<AppState>,
+    Json(request): Json<LoginRequest>,
+) -> std::result::Result<Json<TokenResponse>, Error> {
+    let user: Option<(i64, String)> =
+        sqlx::query_as("SELECT id, password_hash FROM users WHERE email = ?")
+            .bind(normalize_email(&request.email))
+            .fetch_optional(&state.pool)
+            .await?;
+
+    let (id, password_hash) = match user {
+        Some((id, password_hash)) if !password_hash.is_empty() => (Some(id), Some(password_hash)),
+        _ => (None, None),
+    };
+
+    let password = request.password;
+    let valid = blocking(move || {
+        verify_password(&password, password_hash.as_deref().unwrap_or(&DUMMY_HASH))
+    })
+    .await?;
+
+    match id {
+        Some(id) if valid => Ok(Json(state.jwt.issue(id)?)),
+        _ => Err(Error::InvalidCredentials),
+    }
+}
+
+async fn me(user: CurrentUser) -> Json<CurrentUser> {
+    Json(user)
+}
+
+/// Emails are compared case-insensitively, so they're stored trimmed and lowercased.
+fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Run Argon2 on the blocking pool so it doesn't stall the async worker threads.
+async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> T + Send + 'static,
+) -> std::result::Result<T, Error> {
+    Ok(tokio::task::spawn_blocking(f).await?)
+}
+
+fn hash_password(password: &str) -> std::result::Result<String, Error> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    Ok(Argon2::default()
+        .hash_password(password.as_bytes(), &salt)?
+        .to_string())
+}
+
+/// Users created before passwords were introduced have an empty hash and can't log in.
+fn verify_password(password: &str, password_hash: &str) -> bool {
+    PasswordHash::new(password_hash)
+        .map(|hash| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{header::CONTENT_TYPE, Request},
+        response::Response,
+    };
+    use sqlx::sqlite::SqlitePoolOptions;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::limits::{Limits, RateLimiter};
+
+    const SECRET: &str = "test-secret-that-is-at-least-32-bytes";
+
+    async fn test_state() -> AppState {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("../../migrations").run(&pool).await.unwrap();
+
+        AppState {
+            pool,
+            jwt: JwtKeys::new(SECRET, 60).unwrap(),
+            limits: Limits {
+                rate_limiter: RateLimiter::new(0),
+                max_body_bytes: 1024,
+                trust_proxy: false,
+            },
+        }
+    }
+
+    async fn post_json(state: &AppState, uri: &str, body: &str) -> Response {
+        let request = Request::post(uri)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+
+        router()
+            .with_state(state.clone())
+            .oneshot(request)
+            .await
+            .unwrap()
+    }
+
+    async fn get_me(state: &AppState, token: Option<&str>) -> Response {
+        let mut request = Request::get("/auth/me");
+        if let Some(token) = token {
+            request = request.header(AUTHORIZATION, format!("Bearer {token}"));
+        }
+
+        router()
+            .with_state(state.clone())
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn password_hash_round_trip() {
+        let hash = hash_password("correct horse").unwrap();
+
+        assert!(verify_password("correct horse", &hash));
+        assert!(!verify_password("battery staple", &hash));
+        assert!(!verify_password("correct horse", ""));
+    }
+
+    #[test]
+    fn token_round_trip() {
+        let keys = JwtKeys::new(SECRET, 60).unwrap();
+        let token = keys.issue(42).unwrap().access_token;
+
+        assert_eq!(keys.verify(&token).unwrap().sub, "42");
+
+        let other = JwtKeys::new("another-secret-that-is-at-least-32-bytes", 60).unwrap();
+        assert!(other.verify(&token).is_err());
+    }
+
+    #[test]
+    fn short_secrets_are_rejected() {
+        assert!(matches!(JwtKeys::new("", 60), Err(Error::WeakJwtSecret)));
+        assert!(matches!(
+            JwtKeys::new("too-short", 60),
+            Err(Error::WeakJwtSecret)
+        ));
+    }
+
+    #[tokio::test]
+    async fn register_creates_user_once() {
+        let state = test_state().await;
+        let body = r#"{"name":"Ada","email":"ada@example.com","password":"hunter2"}"#;
+
+        let response = post_json(&state, "/auth/register", body).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let response = post_json(&state, "/auth/register", body).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        // Emails differing only in case belong to the same account
+        let body = r#"{"name":"Ada","email":" ADA@example.com ","password":"hunter2"}"#;
+        let response = post_json(&state, "/auth/register", body).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn register_requires_password() {
+        let state = test_state().await;
+        let body = r#"{"name":"Ada","email":"ada@example.com","password":""}"#;
+
+        let response = post_json(&state, "/auth/register", body).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn login_checks_password() {
+        let state = test_state().await;
+        let body = r#"{"name":"Ada","email":"ada@example.com","password":"hunter2"}"#;
+        post_json(&state, "/auth/register", body).await;
+
+        let body = r#"{"email":"ada@example.com","password":"wrong"}"#;
+        let response = post_json(&state, "/auth/login", body).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let body = r#"{"email":"bob@example.com","password":"dummy password"}"#;
+        let response = post_json(&state, "/auth/login", body).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let body = r#"{"email":"Ada@Example.com","password":"hunter2"}"#;
+        let response = post_json(&state, "/auth/login", body).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn current_user_requires_valid_token() {
+        let state = test_state().await;
+
+        assert_eq!(
+            get_me(&state, None).await.status(),
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            get_me(&state, Some("garbage")).await.status(),
+            StatusCode::UNAUTHORIZED
+        );
+
+        let body = r#"{"name":"Ada","email":"ada@example.com","password":"hunter2"}"#;
+        let response = post_json(&state, "/auth/register", body).await;
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let token: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        let token = token["access_token"].as_str().unwrap();
+
+        assert_eq!(get_me(&state, Some(token)).await.status(), StatusCode::OK);
+
+        sqlx::query("DELETE FROM users")
+            .execute(&state.pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            get_me(&state, Some(token)).await.status(),
+            StatusCode::UNAUTHORIZED
+        );
+    }
+}
@@ -1,15 +1,27 @@
-use clap::Parser;
+use std::path::PathBuf;
+
+use crate::prelude::*;
+use clap::{parser::ValueSource, ArgMatches, CommandFactory, FromArgMatches, Parser};
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Parser)]
 #[command(name = "quillai-api")]
 #[command(about = "Quillai API")]
 pub struct App {
+    /// TOML configuration file. Flags and environment variables take precedence over it.
+    #[clap(long, env = "QUILLAI_API_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Print the effective configuration and exit.
+    #[clap(long)]
+    pub print_config: bool,
+
     /// Host to attach the service to.
-    #[clap(long, default_value = "127.0.0.1")]
+    #[clap(long, default_value = "127.0.0.1", env = "QUILLAI_API_HOST")]
     pub host: String,
 
     /// Port the API should listen.
-    #[clap(long, default_value = "2908")]
+    #[clap(long, default_value = "2908", env = "QUILLAI_API_PORT")]
     pub port: u32,
 
     /// Database host url
@@ -24,6 +36,31 @@ pub struct App {
     #[clap(long, default_value = "3", env = "QUILLAI_API_DB_ACQUIRE_TIMEOUT")]
     pub db_acquire_timeout: u64,
 
+    /// Secret used to sign JWT access tokens
+    #[clap(long, env = "QUILLAI_API_JWT_SECRET", hide_env_values = true)]
+    pub jwt_secret: Option<String>,
+
+    /// JWT access token lifetime in seconds
+    #[clap(long, default_value = "3600", env = "QUILLAI_API_JWT_TTL")]
+    pub jwt_ttl: u64,
+
+    /// Mutating requests allowed per minute for each user or IP (0 disables the limit)
+    #[clap(long, default_value = "120", env = "QUILLAI_API_RATE_LIMIT")]
+    pub rate_limit: u32,
+
+    /// Maximum request body size in bytes
+    #[clap(long, default_value = "1048576", env = "QUILLAI_API_MAX_BODY_BYTES")]
+    pub max_body_bytes: usize,
+
+    /// Rate limit anonymous clients by the last `X-Forwarded-For` address. Only enable
+    /// behind a single trusted reverse proxy.
+    #[clap(
+        long,
+        env = "QUILLAI_API_TRUST_PROXY",
+        value_parser = clap::builder::BoolishValueParser::new()
+    )]
+    pub trust_proxy: bool,
+
     /// Log level
     #[clap(
         long,
@@ -32,4 +69,241 @@ pub struct App {
         env = "QUILLAI_API_LOG_LEVEL"
     )]
     pub log_level: quillai_log::LogLevel,
+
+    /// Directory to write daily rotated log files to (logs to stdout if not set)
+    #[clap(long, env = "QUILLAI_API_LOG_DIR")]
+    pub log_dir: Option<PathBuf>,
+}
+
+impl App {
+    /// Parse flags and environment variables, then fill the remaining values from the
+    /// configuration file if one was given.
+    pub fn load() -> Result<Self> {
+        Self::from_matches(&Self::command().get_matches())
+    }
+
+    /// Build the configuration from already parsed flags and environment variables.
+    pub fn from_matches(matches: &ArgMatches) -> Result<Self> {
+        let mut app = Self::from_arg_matches(matches)?;
+
+        if let Some(path) = app.config.clone() {
+            let contents = std::fs::read_to_string(&path)
+                .with_context(|| format!("failed to read config file {}", path.display()))?;
+            let file: ConfigFile = toml::from_str(&contents)
+                .with_context(|| format!("invalid config file {}", path.display()))?;
+
+            app.merge(file, matches)
+                .with_context(|| format!("invalid config file {}", path.display()))?;
+        }
+
+        Ok(app)
+    }
+
+    fn merge(&mut self, file: ConfigFile, matches: &ArgMatches) -> Result<()> {
+        // Values passed explicitly through a flag or the environment win over the file.
+        let unset = |id: &str| {
+            matches!(
+                matches.value_source(id),
+                None | Some(ValueSource::DefaultValue)
+            )
+        };
+
+        layer(&mut self.host, file.server.host, unset("host"));
+        layer(&mut self.port, file.server.port, unset("port"));
+        layer(&mut self.db_url, file.database.url, unset("db_url"));
+        layer(
+            &mut self.db_max_connections,
+            file.database.max_connections,
+            unset("db_max_connections"),
+        );
+        layer(
+            &mut self.db_acquire_timeout,
+            file.database.acquire_timeout,
+            unset("db_acquire_timeout"),
+        );
+        layer(
+            &mut self.jwt_secret,
+            file.auth.jwt_secret.map(Some),
+            unset("jwt_secret"),
+        );
+        layer(&mut self.jwt_ttl, file.auth.jwt_ttl, unset("jwt_ttl"));
+        layer(
+            &mut self.rate_limit,
+            file.limits.rate_limit,
+            unset("rate_limit"),
+        );
+        layer(
+            &mut self.max_body_bytes,
+            file.limits.max_body_bytes,
+            unset("max_body_bytes"),
+        );
+        layer(
+            &mut self.trust_proxy,
+            file.limits.trust_proxy,
+            unset("trust_proxy"),
+        );
+        layer(
+            &mut self.log_level,
+            file.log.level.map(|level| level.parse()).transpose()?,
+            unset("log_level"),
+        );
+        layer(&mut self.log_dir, file.log.dir.map(Some), unset("log_dir"));
+
+        Ok(())
+    }
+
+    /// The configuration in effect, in config file layout. The JWT secret is redacted.
+    pub fn effective_config(&self) -> ConfigFile {
+        ConfigFile {
+            server: ServerSection {
+                host: Some(self.host.clone()),
+                port: Some(self.port),
+            },
+            database: DatabaseSection {
+                url: Some(self.db_url.clone()),
+                max_connections: Some(self.db_max_connections),
+                acquire_timeout: Some(self.db_acquire_timeout),
+            },
+            auth: AuthSection {
+                jwt_secret: self.jwt_secret.as_ref().map(|_| "<redacted>".to_string()),
+                jwt_ttl: Some(self.jwt_ttl),
+            },
+            limits: LimitsSection {
+                rate_limit: Some(self.rate_limit),
+                max_body_bytes: Some(self.max_body_bytes),
+                trust_proxy: Some(self.trust_proxy),
+            },
+            log: LogSection {
+                level: Some(self.log_level.to_string()),
+                dir: self.log_dir.clone(),
+            },
+        }
+    }
+}
+
+fn layer<T>(target: &mut T, value: Option<T>, unset: bool) {
+    if let (true, Some(value)) = (unset, value) {
+        *target = value;
+    }
+}
+
+/// Configuration file layout. Every key is optional.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    pub server: ServerSection,
+    pub database: DatabaseSection,
+    pub auth: AuthSection,
+    pub limits: LimitsSection,
+    pub log: LogSection,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerSection {
+    pub host: Option<String>,
+    pub port: Option<u32>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseSection {
+    pub url: Option<String>,
+    pub max_connections: Option<u32>,
+    pub acquire_timeout: Option<u64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthSection {
+    pub jwt_secret: Option<String>,
+    pub jwt_ttl: Option<u64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsSection {
+    pub rate_limit: Option<u32>,
+    pub max_body_bytes: Option<usize>,
+    pub trust_proxy: Option<bool>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogSection {
+    pub level: Option<String>,
+    pub dir: Option<PathBuf>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write `contents` to a config file unique to the calling test.
+    fn config_file(name: &str, contents: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("quillai_api_{name}_{}.toml", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    /// Parse `args` without reading `QUILLAI_API_*` variables, so the results don't depend
+    /// on the environment the tests run in. Environment handling is covered by
+    /// `tests/config.rs` against the built binary.
+    fn load_from(args: &[&str]) -> Result<App> {
+        let matches = App::command()
+            .mut_args(|arg| arg.env(None))
+            .try_get_matches_from(std::iter::once("quillai-api").chain(args.iter().copied()))?;
+        App::from_matches(&matches)
+    }
+
+    #[test]
+    fn file_overrides_default() {
+        let path = config_file("file_overrides_default", "[server]\nport = 9000\n");
+        let app = load_from(&["--config", path.to_str().unwrap()]).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(app.port, 9000);
+        assert_eq!(app.host, "127.0.0.1");
+    }
+
+    #[test]
+    fn flag_overrides_file() {
+        let path = config_file("flag_overrides_file", "[server]\nport = 9000\n");
+        let app = load_from(&["--config", path.to_str().unwrap(), "--port", "9001"]).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(app.port, 9001);
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        let path = config_file("unknown_keys_are_rejected", "[server]\nhostname = \"x\"\n");
+        let error = load_from(&["--config", path.to_str().unwrap()]).unwrap_err();
+        let _ = std::fs::remove_file(&path);
+
+        assert!(error.to_string().contains(path.to_str().unwrap()));
+    }
+
+    #[test]
+    fn invalid_log_level_names_the_file() {
+        let path = config_file(
+            "invalid_log_level_names_the_file",
+            "[log]\nlevel = \"loud\"\n",
+        );
+        let error = load_from(&["--config", path.to_str().unwrap()]).unwrap_err();
+        let _ = std::fs::remove_file(&path);
+
+        assert!(error.to_string().contains(path.to_str().unwrap()));
+        assert!(format!("{error:#}").contains("invalid log level: loud"));
+    }
+
+    #[test]
+    fn effective_config_redacts_secret() {
+        let app = load_from(&["--jwt-secret", "super-secret-value"]).unwrap();
+        let config = toml::to_string(&app.effective_config()).unwrap();
+
+        assert!(config.contains("jwt_secret = \"<redacted>\""));
+        assert!(!config.contains("super-secret-value"));
+    }
 }
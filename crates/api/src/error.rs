@@ -1,13 +1,101 @@
-use axum::http::StatusCode;
+use std::time::Duration;
 
-#[derive(thiserror::Error, axum_thiserror::ErrorStatus)]
+use axum::{
+    extract::rejection::JsonRejection,
+    http::{header::RETRY_AFTER, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+#[derive(thiserror::Error)]
 pub enum Error {
     #[error("sqlx error")]
-    #[status(StatusCode::INTERNAL_SERVER_ERROR)]
     Sqlx(#[from] sqlx::Error),
     #[error("invalid log level")]
-    #[status(StatusCode::INTERNAL_SERVER_ERROR)]
     InvalidLogLevel(#[from] quillai_log::Error),
+    #[error("migration error")]
+    Migrate(#[from] sqlx::migrate::MigrateError),
+    #[error("password hash error")]
+    PasswordHash(#[from] argon2::password_hash::Error),
+    #[error("token error")]
+    Jwt(#[from] jsonwebtoken::errors::Error),
+    #[error("background task failed")]
+    Join(#[from] tokio::task::JoinError),
+    #[error(
+        "JWT secret must be at least {} bytes long",
+        crate::auth::MIN_SECRET_LEN
+    )]
+    WeakJwtSecret,
+    #[error("{}", .0.body_text())]
+    InvalidJson(JsonRejection),
+    #[error("invalid request: {0}")]
+    InvalidRequest(&'static str),
+    #[error("email already registered")]
+    EmailTaken,
+    #[error("invalid email or password")]
+    InvalidCredentials,
+    #[error("unauthorized")]
+    Unauthorized,
+    /// Carries how long until the client may retry.
+    #[error("too many requests")]
+    RateLimited(Duration),
+    #[error("payload too large")]
+    PayloadTooLarge,
+}
+
+impl Error {
+    /// HTTP status returned to the client for this error.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Error::InvalidJson(rejection) => rejection.status(),
+            Error::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            Error::EmailTaken => StatusCode::CONFLICT,
+            Error::InvalidCredentials | Error::Unauthorized => StatusCode::UNAUTHORIZED,
+            Error::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            Error::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            Error::Sqlx(_)
+            | Error::InvalidLogLevel(_)
+            | Error::Migrate(_)
+            | Error::PasswordHash(_)
+            | Error::Jwt(_)
+            | Error::Join(_)
+            | Error::WeakJwtSecret => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+/// Every error is returned as `{"error": "<message>"}`.
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let body = ErrorBody {
+            error: self.to_string(),
+        };
+        let mut response = (self.status(), Json(body)).into_response();
+
+        if let Error::RateLimited(retry_after) = self {
+            // Retry-After only takes whole seconds; round up so the retry finds a token.
+            let seconds = retry_after.as_secs_f64().ceil() as u64;
+            response.headers_mut().insert(RETRY_AFTER, seconds.into());
+        }
+
+        response
+    }
+}
+
+/// Bodies over the `DefaultBodyLimit` are reported like any other oversized payload.
+impl From<JsonRejection> for Error {
+    fn from(rejection: JsonRejection) -> Self {
+        match rejection.status() {
+            StatusCode::PAYLOAD_TOO_LARGE => Error::PayloadTooLarge,
+            _ => Error::InvalidJson(rejection),
+        }
+    }
 }
 
 /// Format error messages for display
@@ -0,0 +1,20 @@
+//! Extractors whose rejections are reported as [`Error`]s.
+use axum::{
+    extract::FromRequest,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+
+use crate::prelude::*;
+
+/// `axum::Json` that rejects malformed or oversized bodies with an [`Error`].
+#[derive(FromRequest)]
+#[from_request(via(axum::Json), rejection(Error))]
+pub struct Json<T>(pub T);
+
+impl<T: Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> Response {
+        let Self(value) = self;
+        axum::Json(value).into_response()
+    }
+}
@@ -0,0 +1,396 @@
+//! Request limits
+//!
+//! Mutating requests are rate limited per user (when a valid access token is
+//! present) or per client IP, using a token bucket that refills continuously.
+//! Request bodies larger than the configured maximum are rejected up front.
+//!
+//! The client IP is the peer address of the connection. Behind a reverse proxy
+//! that is the proxy's address, so every anonymous client would share one bucket;
+//! enable `trust_proxy` to read it from the last `X-Forwarded-For` entry instead.
+//! Only do so when a single proxy you control sits in front of the API, as
+//! clients can otherwise set the header themselves.
+//!
+//! IPv6 clients are limited per /64, the smallest block usually handed to a single
+//! site, so rotating through addresses within it doesn't buy more requests.
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header::CONTENT_LENGTH, HeaderMap, Method},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::prelude::*;
+use crate::state::AppState;
+
+/// How often buckets that have refilled completely are dropped.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Limits applied to every request.
+#[derive(Clone)]
+pub struct Limits {
+    pub rate_limiter: RateLimiter,
+    pub max_body_bytes: usize,
+    /// Take the client IP from `X-Forwarded-For` rather than the peer address.
+    pub trust_proxy: bool,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+struct Buckets {
+    by_key: HashMap<String, Bucket>,
+    swept: Instant,
+}
+
+/// Token bucket rate limiter keyed by user or IP.
+#[derive(Clone)]
+pub struct RateLimiter {
+    per_minute: u32,
+    buckets: Arc<Mutex<Buckets>>,
+}
+
+impl RateLimiter {
+    /// Allow `per_minute` requests per key, with bursts up to the same amount. `0` disables it.
+    pub fn new(per_minute: u32) -> Self {
+        Self {
+            per_minute,
+            buckets: Arc::new(Mutex::new(Buckets {
+                by_key: HashMap::new(),
+                swept: Instant::now(),
+            })),
+        }
+    }
+
+    /// Take a token for `key`, returning how long until one is available if its bucket is empty.
+    pub fn check(&self, key: &str) -> std::result::Result<(), Duration> {
+        if self.per_minute == 0 {
+            return Ok(());
+        }
+
+        let capacity = f64::from(self.per_minute);
+        let refill_per_sec = capacity / 60.0;
+        let now = Instant::now();
+
+        let mut buckets = self
+            .buckets
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if now.duration_since(buckets.swept) >= SWEEP_INTERVAL {
+            // Buckets that would have refilled completely carry no state worth keeping.
+            buckets.by_key.retain(|_, bucket| {
+                bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * refill_per_sec
+                    < capacity
+            });
+            buckets.swept = now;
+        }
+
+        let bucket = buckets.by_key.entry(key.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+        });
+
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_per_sec).min(capacity);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - bucket.tokens) / refill_per_sec,
+            ))
+        }
+    }
+}
+
+/// Middleware enforcing [`Limits`].
+pub async fn enforce(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> std::result::Result<Response, Error> {
+    let content_length = request
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+
+    if content_length.is_some_and(|length| length > state.limits.max_body_bytes) {
+        return Err(Error::PayloadTooLarge);
+    }
+
+    if !matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    ) {
+        let key = rate_limit_key(&state, &request);
+
+        state
+            .limits
+            .rate_limiter
+            .check(&key)
+            .map_err(Error::RateLimited)?;
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// Bucket key for a request: the user if it carries a valid token, its client IP otherwise.
+fn rate_limit_key(state: &AppState, request: &Request) -> String {
+    if let Some(claims) =
+        crate::auth::bearer_token(request.headers()).and_then(|token| state.jwt.verify(token).ok())
+    {
+        return format!("user:{}", claims.sub);
+    }
+
+    let forwarded = state
+        .limits
+        .trust_proxy
+        .then(|| forwarded_ip(request.headers()))
+        .flatten();
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+
+    match forwarded.or(peer) {
+        Some(IpAddr::V6(ip)) if ip.to_ipv4_mapped().is_none() => {
+            let network = Ipv6Addr::from(u128::from(ip) & (u128::MAX << 64));
+            format!("ip:{network}/64")
+        }
+        Some(ip) => format!("ip:{}", ip.to_canonical()),
+        None => "ip:unknown".to_string(),
+    }
+}
+
+/// The address the proxy appended to `X-Forwarded-For`, i.e. the last entry.
+fn forwarded_ip(headers: &HeaderMap) -> Option<IpAddr> {
+    headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .next_back()
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.rsplit(',').next())
+        .and_then(|ip| ip.trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        extract::DefaultBodyLimit,
+        http::{
+            header::{AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER},
+            StatusCode,
+        },
+        middleware,
+        routing::post,
+        Router,
+    };
+    use sqlx::sqlite::SqlitePool;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::auth::JwtKeys;
+    use crate::extract::Json;
+
+    const SECRET: &str = "test-secret-that-is-at-least-32-bytes";
+
+    fn test_app(rate_limit: u32, trust_proxy: bool) -> Router {
+        let state = AppState {
+            pool: SqlitePool::connect_lazy("sqlite::memory:").unwrap(),
+            jwt: JwtKeys::new(SECRET, 60).unwrap(),
+            limits: Limits {
+                rate_limiter: RateLimiter::new(rate_limit),
+                max_body_bytes: 16,
+                trust_proxy,
+            },
+        };
+
+        Router::new()
+            .route(
+                "/",
+                post(|Json(body): Json<serde_json::Value>| async move { Json(body) }),
+            )
+            .route(
+                "/quota",
+                post(|| async { (StatusCode::PAYLOAD_TOO_LARGE, "quota exceeded") }),
+            )
+            .layer(middleware::from_fn_with_state(state.clone(), enforce))
+            .layer(DefaultBodyLimit::max(state.limits.max_body_bytes))
+            .with_state(state)
+    }
+
+    async fn send(app: &Router, request: axum::http::request::Builder, body: &str) -> Response {
+        let request = request.header(CONTENT_TYPE, "application/json");
+        app.clone()
+            .oneshot(request.body(Body::from(body.to_string())).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn error_message(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+
+        body["error"].as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn rate_limiter_rejects_after_burst() {
+        let limiter = RateLimiter::new(3);
+
+        assert!(limiter.check("ip:127.0.0.1").is_ok());
+        assert!(limiter.check("ip:127.0.0.1").is_ok());
+        assert!(limiter.check("ip:127.0.0.1").is_ok());
+        // 3 per minute refills a token every 20 seconds
+        let wait = limiter.check("ip:127.0.0.1").unwrap_err();
+        assert!(wait > Duration::from_secs(19) && wait <= Duration::from_secs(20));
+        assert!(limiter.check("ip:127.0.0.2").is_ok());
+    }
+
+    #[test]
+    fn rate_limiter_disabled_with_zero() {
+        let limiter = RateLimiter::new(0);
+
+        assert!((0..1000).all(|_| limiter.check("user:1").is_ok()));
+    }
+
+    #[tokio::test]
+    async fn oversized_content_length_is_rejected() {
+        let app = test_app(0, false);
+        let request = Request::post("/").header(CONTENT_LENGTH, 17);
+
+        let response = send(&app, request, &format!("\"{}\"", "x".repeat(15))).await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(error_message(response).await, "payload too large");
+    }
+
+    #[tokio::test]
+    async fn oversized_body_without_content_length_is_rejected() {
+        let app = test_app(0, false);
+
+        let response = send(&app, Request::post("/"), &format!("\"{}\"", "x".repeat(15))).await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(error_message(response).await, "payload too large");
+    }
+
+    #[tokio::test]
+    async fn rate_limited_response_has_retry_after() {
+        let app = test_app(1, false);
+
+        assert_eq!(
+            send(&app, Request::post("/"), "{}").await.status(),
+            StatusCode::OK
+        );
+
+        let response = send(&app, Request::post("/"), "{}").await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[RETRY_AFTER], "60");
+        assert_eq!(error_message(response).await, "too many requests");
+    }
+
+    #[tokio::test]
+    async fn valid_token_is_limited_per_user() {
+        let app = test_app(1, false);
+        let token = JwtKeys::new(SECRET, 60)
+            .unwrap()
+            .issue(1)
+            .unwrap()
+            .access_token;
+        let authorized = || Request::post("/").header(AUTHORIZATION, format!("Bearer {token}"));
+
+        // Exhaust the anonymous `ip:` bucket
+        send(&app, Request::post("/"), "{}").await;
+        assert_eq!(
+            send(&app, Request::post("/"), "{}").await.status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        // The token moves the request to its own `user:` bucket
+        assert_eq!(
+            send(&app, authorized(), "{}").await.status(),
+            StatusCode::OK
+        );
+        assert_eq!(
+            send(&app, authorized(), "{}").await.status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+    }
+
+    #[tokio::test]
+    async fn forwarded_for_is_only_used_when_trusted() {
+        let forwarded =
+            |ip: &str| Request::post("/").header("x-forwarded-for", format!("203.0.113.9, {ip}"));
+
+        let app = test_app(1, false);
+        send(&app, forwarded("198.51.100.1"), "{}").await;
+        assert_eq!(
+            send(&app, forwarded("198.51.100.2"), "{}").await.status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+
+        let app = test_app(1, true);
+        send(&app, forwarded("198.51.100.1"), "{}").await;
+        assert_eq!(
+            send(&app, forwarded("198.51.100.2"), "{}").await.status(),
+            StatusCode::OK
+        );
+        assert_eq!(
+            send(&app, forwarded("198.51.100.1"), "{}").await.status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+    }
+
+    #[tokio::test]
+    async fn handler_payload_too_large_is_passed_through() {
+        let app = test_app(0, false);
+
+        let response = send(&app, Request::post("/quota"), "{}").await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&bytes[..], b"quota exceeded");
+    }
+
+    #[tokio::test]
+    async fn malformed_json_is_a_json_error() {
+        let app = test_app(0, false);
+
+        let response = send(&app, Request::post("/"), "{").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert!(error_message(response).await.contains("JSON"));
+    }
+
+    #[tokio::test]
+    async fn ipv6_clients_share_a_bucket_per_64() {
+        let forwarded = |ip: &str| Request::post("/").header("x-forwarded-for", ip);
+        let app = test_app(1, true);
+
+        send(&app, forwarded("2001:db8::1"), "{}").await;
+        assert_eq!(
+            send(&app, forwarded("2001:db8::ffff:2"), "{}")
+                .await
+                .status(),
+            StatusCode::TOO_MANY_REQUESTS
+        );
+        assert_eq!(
+            send(&app, forwarded("2001:db8:0:1::1"), "{}")
+                .await
+                .status(),
+            StatusCode::OK
+        );
+    }
+}
@@ -7,43 +7,96 @@
 //! ```not_rust
 //! cargo run -p auto-load
 //! ```
-use axum::{extract::State, response::Html, routing::get, Router};
-use clap::Parser;
+use std::str::FromStr;
+
+use axum::{
+    extract::{DefaultBodyLimit, State},
+    middleware,
+    response::Html,
+    routing::get,
+    Router,
+};
 use listenfd::ListenFd;
-use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
 use tokio::net::TcpListener;
 
+mod auth;
 mod cli;
 mod error;
+mod extract;
+mod limits;
 mod prelude;
+mod state;
 
 use crate::prelude::*;
+use crate::state::AppState;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // ╭─────────────────────────────────────────────────────────────────────────────╮
     // │ App                                                                         │
     // ╰─────────────────────────────────────────────────────────────────────────────╯
-    let args = crate::cli::App::parse();
+    let args = crate::cli::App::load()?;
+
+    if args.print_config {
+        print!("{}", toml::to_string(&args.effective_config())?);
+        return Ok(());
+    }
+
+    let jwt_secret = args.jwt_secret.as_deref().context(
+        "missing JWT secret: set --jwt-secret, QUILLAI_API_JWT_SECRET or auth.jwt_secret",
+    )?;
+    let jwt = crate::auth::JwtKeys::new(jwt_secret, args.jwt_ttl)?;
 
     // ╭─────────────────────────────────────────────────────────────────────────────╮
     // │ Logger                                                                      │
     // ╰─────────────────────────────────────────────────────────────────────────────╯
-    quillai_log::init_simple_logger(args.log_level)?;
+    let log_config = quillai_log::LogConfig {
+        output: match &args.log_dir {
+            Some(directory) => {
+                quillai_log::LogOutput::File(quillai_log::FileOutput::new(directory, "api.log"))
+            }
+            None => quillai_log::LogOutput::Stdout,
+        },
+        ..Default::default()
+    };
+    let _log_guard = quillai_log::init_logger(args.log_level, &log_config)?;
 
     // ╭─────────────────────────────────────────────────────────────────────────────╮
     // │ DB                                                                          │
     // ╰─────────────────────────────────────────────────────────────────────────────╯
+    // Create the database file on first start instead of failing to open it.
+    let connect_options = SqliteConnectOptions::from_str(&args.db_url)?.create_if_missing(true);
     let pool = SqlitePoolOptions::new()
         .max_connections(args.db_max_connections)
         .acquire_timeout(std::time::Duration::from_secs(args.db_acquire_timeout))
-        .connect(&args.db_url)
+        .connect_with(connect_options)
         .await?;
 
+    sqlx::migrate!("../../migrations").run(&pool).await?;
+
     // ╭─────────────────────────────────────────────────────────────────────────────╮
     // │ Router                                                                      │
     // ╰─────────────────────────────────────────────────────────────────────────────╯
-    let app = Router::new().route("/", get(handler)).with_state(pool);
+    let state = AppState {
+        pool,
+        jwt,
+        limits: crate::limits::Limits {
+            rate_limiter: crate::limits::RateLimiter::new(args.rate_limit),
+            max_body_bytes: args.max_body_bytes,
+            trust_proxy: args.trust_proxy,
+        },
+    };
+
+    let app = Router::new()
+        .route("/", get(handler))
+        .merge(crate::auth::router())
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            crate::limits::enforce,
+        ))
+        .layer(DefaultBodyLimit::max(args.max_body_bytes))
+        .with_state(state);
 
     // ╭─────────────────────────────────────────────────────────────────────────────╮
     // │ Dev mode                                                                    │
@@ -70,7 +123,11 @@ async fn main() -> Result<()> {
     let local_addr = listener.local_addr()?;
     quillai_log::info!("Listening on {}", local_addr);
 
-    axum::serve(listener, app).await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
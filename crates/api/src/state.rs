@@ -0,0 +1,25 @@
+use axum::extract::FromRef;
+use sqlx::sqlite::SqlitePool;
+
+use crate::auth::JwtKeys;
+use crate::limits::Limits;
+
+/// Shared state handed to every handler.
+#[derive(Clone)]
+pub struct AppState {
+    pub pool: SqlitePool,
+    pub jwt: JwtKeys,
+    pub limits: Limits,
+}
+
+impl FromRef<AppState> for SqlitePool {
+    fn from_ref(state: &AppState) -> Self {
+        state.pool.clone()
+    }
+}
+
+impl FromRef<AppState> for JwtKeys {
+    fn from_ref(state: &AppState) -> Self {
+        state.jwt.clone()
+    }
+}
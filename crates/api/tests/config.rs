@@ -0,0 +1,78 @@
+//! Environment variables are checked against the built binary so each case sets them
+//! on a child process rather than on the shared test process.
+use std::process::Command;
+
+/// Run `api --print-config` with only the given `QUILLAI_API_*` variables set.
+fn print_config(env: &[(&str, &str)], args: &[&str]) -> toml::Table {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_api"));
+    for (key, _) in std::env::vars_os() {
+        if key.to_string_lossy().starts_with("QUILLAI_API_") {
+            command.env_remove(key);
+        }
+    }
+
+    let output = command
+        .envs(env.iter().copied())
+        .arg("--print-config")
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    toml::from_str(&String::from_utf8(output.stdout).unwrap()).unwrap()
+}
+
+#[test]
+fn env_overrides_file() {
+    let path = std::env::temp_dir().join(format!(
+        "quillai_api_env_overrides_file_{}.toml",
+        std::process::id()
+    ));
+    std::fs::write(&path, "[server]\nport = 9000\n\n[auth]\njwt_ttl = 10\n").unwrap();
+
+    let config = print_config(
+        &[("QUILLAI_API_JWT_TTL", "20")],
+        &["--config", path.to_str().unwrap()],
+    );
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(config["auth"]["jwt_ttl"].as_integer(), Some(20));
+    assert_eq!(config["server"]["port"].as_integer(), Some(9000));
+}
+
+#[test]
+fn host_and_port_from_env() {
+    let config = print_config(
+        &[
+            ("QUILLAI_API_HOST", "0.0.0.0"),
+            ("QUILLAI_API_PORT", "4000"),
+        ],
+        &[],
+    );
+
+    assert_eq!(config["server"]["host"].as_str(), Some("0.0.0.0"));
+    assert_eq!(config["server"]["port"].as_integer(), Some(4000));
+}
+
+#[test]
+fn trust_proxy_accepts_boolish_env_values() {
+    for (value, expected) in [
+        ("1", true),
+        ("yes", true),
+        ("true", true),
+        ("0", false),
+        ("no", false),
+    ] {
+        let config = print_config(&[("QUILLAI_API_TRUST_PROXY", value)], &[]);
+
+        assert_eq!(
+            config["limits"]["trust_proxy"].as_bool(),
+            Some(expected),
+            "QUILLAI_API_TRUST_PROXY={value}"
+        );
+    }
+}
@@ -16,7 +16,7 @@
 //! ```
 
 use quillai_log::{
-    LogLevel, LogConfig, LogFormat, init_logger, init_simple_logger,
+    LogLevel, LogConfig, LogFormat, LogOutput, init_logger, init_simple_logger,
     info, debug, warn, error, trace,
     info_span, debug_span, create_span, Level, Instrument
 };
@@ -30,11 +30,6 @@ struct User {
     email: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct ApiResponse {
-    users: Vec<User>,
-}
-
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🚀 QuillAI Log Comprehensive Example");
@@ -236,6 +231,7 @@ async fn demo_external_crate_integration() -> Result<(), Box<dyn std::error::Err
         with_line_number: false,
         with_thread_names: false,
         env_filter: Some("quillai_log=debug,reqwest=debug,hyper=info".to_string()),
+        output: LogOutput::Stdout,
     };
     
     // Note: This will fail to initialize if subscriber is already set, which is fine
@@ -8,7 +8,7 @@
 //! cargo run --example json_output --features json
 //! ```
 
-use quillai_log::{LogLevel, LogConfig, LogFormat, init_logger, info, debug, warn, error};
+use quillai_log::{LogLevel, LogConfig, LogFormat, LogOutput, init_logger, info, debug, warn, error};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("📄 QuillAI Log - JSON Output Example");
@@ -23,10 +23,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         with_thread_names: false,
         with_line_number: true,
         env_filter: None,
+        output: LogOutput::Stdout,
     };
     
     // Initialize with JSON formatting
-    init_logger(LogLevel::Debug, &config)?;
+    let _guard = init_logger(LogLevel::Debug, &config)?;
     
     // Application lifecycle events
     info!(
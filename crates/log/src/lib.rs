@@ -9,17 +9,25 @@
 //! ```rust
 //! use quillai_log::{LogLevel, LogConfig, init_logger};
 //!
-//! // Initialize with default configuration
-//! init_logger(LogLevel::Info, &LogConfig::default()).unwrap();
+//! // Initialize with default configuration. Keep the guard alive for as long as
+//! // the application logs, so buffered file output gets flushed on shutdown.
+//! let _guard = init_logger(LogLevel::Info, &LogConfig::default()).unwrap();
 //!
 //! // Use tracing macros
 //! tracing::info!("Hello, world!");
 //! tracing::debug!(user_id = 42, "User logged in");
 //! ```
 
+use std::path::PathBuf;
+
 use clap::ValueEnum;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
 
+mod rolling;
+
 // Re-export tracing macros for convenience
 pub use tracing::{debug, error, info, trace, warn};
 pub use tracing::{debug_span, error_span, info_span, trace_span, warn_span};
@@ -33,6 +41,10 @@ pub enum Error {
     InitializationFailed(String),
     #[error("tracing subscriber error: {0}")]
     TracingSubscriber(#[from] tracing_subscriber::util::TryInitError),
+    #[error("log file appender error: {0}")]
+    FileAppender(#[from] tracing_appender::rolling::InitError),
+    #[error("log file error: {0}")]
+    Io(#[from] std::io::Error),
 }
 
 /// Format error messages for display
@@ -153,6 +165,8 @@ pub struct LogConfig {
     pub with_line_number: bool,
     /// Custom environment filter (overrides log level if set)
     pub env_filter: Option<String>,
+    /// Where log records are written
+    pub output: LogOutput,
 }
 
 /// Output format options
@@ -166,6 +180,58 @@ pub enum LogFormat {
     Json,
 }
 
+/// Output destination options
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum LogOutput {
+    /// Write to standard output
+    #[default]
+    Stdout,
+    /// Write to standard error
+    Stderr,
+    /// Write to a rotating log file through a non-blocking writer
+    File(FileOutput),
+}
+
+/// File output configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileOutput {
+    /// Directory holding the log files (created if missing)
+    pub directory: PathBuf,
+    /// Name of the log file, used as prefix for time-rotated files
+    pub file_name: String,
+    /// When to rotate the log file
+    pub rotation: LogRotation,
+    /// Maximum number of log files to keep, counting the active one (unbounded if not set or 0)
+    pub max_files: Option<usize>,
+}
+
+impl FileOutput {
+    /// Create a daily-rotated file output
+    pub fn new(directory: impl Into<PathBuf>, file_name: impl Into<String>) -> Self {
+        Self {
+            directory: directory.into(),
+            file_name: file_name.into(),
+            rotation: LogRotation::Daily,
+            max_files: None,
+        }
+    }
+}
+
+/// Log file rotation policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogRotation {
+    /// Rotate every minute
+    Minutely,
+    /// Rotate every hour
+    Hourly,
+    /// Rotate every day
+    Daily,
+    /// Rotate once the file grows past the given number of bytes
+    Size(u64),
+    /// Never rotate
+    Never,
+}
+
 impl Default for LogConfig {
     fn default() -> Self {
         Self {
@@ -175,12 +241,64 @@ impl Default for LogConfig {
             with_thread_names: false,
             with_line_number: false,
             env_filter: None,
+            output: LogOutput::default(),
         }
     }
 }
 
+/// Keeps the non-blocking log writer alive
+///
+/// Dropping the guard flushes any buffered records, so hold on to it until the
+/// application exits.
+#[must_use = "dropping the guard stops the non-blocking log writer"]
+#[derive(Debug)]
+pub struct LogGuard {
+    _worker: Option<WorkerGuard>,
+}
+
+/// Build the writer for the configured output
+fn make_writer(output: &LogOutput) -> Result<(BoxMakeWriter, Option<WorkerGuard>), Error> {
+    let file = match output {
+        LogOutput::Stdout => return Ok((BoxMakeWriter::new(std::io::stdout), None)),
+        LogOutput::Stderr => return Ok((BoxMakeWriter::new(std::io::stderr), None)),
+        LogOutput::File(file) => file,
+    };
+
+    let (writer, guard) = match file.rotation {
+        LogRotation::Size(max_bytes) => {
+            tracing_appender::non_blocking(rolling::SizeRollingWriter::new(
+                &file.directory,
+                &file.file_name,
+                max_bytes,
+                file.max_files,
+            )?)
+        }
+        rotation => {
+            let rotation = match rotation {
+                LogRotation::Minutely => Rotation::MINUTELY,
+                LogRotation::Hourly => Rotation::HOURLY,
+                LogRotation::Daily => Rotation::DAILY,
+                LogRotation::Never | LogRotation::Size(_) => Rotation::NEVER,
+            };
+
+            let mut builder = RollingFileAppender::builder()
+                .rotation(rotation)
+                .filename_prefix(file.file_name.as_str());
+            if let Some(max_files) = file.max_files.filter(|&max| max > 0) {
+                builder = builder.max_log_files(max_files);
+            }
+
+            tracing_appender::non_blocking(builder.build(&file.directory)?)
+        }
+    };
+
+    Ok((BoxMakeWriter::new(writer), Some(guard)))
+}
+
 /// Initialize the global tracing subscriber
-pub fn init_logger(level: LogLevel, config: &LogConfig) -> Result<(), Error> {
+///
+/// The returned guard must be kept alive while logging to a file.
+pub fn init_logger(level: LogLevel, config: &LogConfig) -> Result<LogGuard, Error> {
     let env_filter = if let Some(ref filter) = config.env_filter {
         EnvFilter::try_from_default_env()
             .or_else(|_| EnvFilter::try_new(filter))
@@ -191,12 +309,22 @@ pub fn init_logger(level: LogLevel, config: &LogConfig) -> Result<(), Error> {
             .map_err(|e| Error::InitializationFailed(e.to_string()))?
     };
 
+    let (writer, worker) = make_writer(&config.output)?;
+    // Files never get colour; stdout and stderr keep the library default, which honours `NO_COLOR`.
+    let plain = matches!(config.output, LogOutput::File(_));
+
     match config.format {
         LogFormat::Pretty => {
             let fmt_layer = fmt::layer()
                 .with_target(config.with_target)
                 .with_thread_names(config.with_thread_names)
-                .with_line_number(config.with_line_number);
+                .with_line_number(config.with_line_number)
+                .with_writer(writer);
+            let fmt_layer = if plain {
+                fmt_layer.with_ansi(false)
+            } else {
+                fmt_layer
+            };
 
             let fmt_layer = if config.with_timestamp {
                 fmt_layer.boxed()
@@ -214,7 +342,13 @@ pub fn init_logger(level: LogLevel, config: &LogConfig) -> Result<(), Error> {
                 .compact()
                 .with_target(config.with_target)
                 .with_thread_names(config.with_thread_names)
-                .with_line_number(config.with_line_number);
+                .with_line_number(config.with_line_number)
+                .with_writer(writer);
+            let fmt_layer = if plain {
+                fmt_layer.with_ansi(false)
+            } else {
+                fmt_layer
+            };
 
             let fmt_layer = if config.with_timestamp {
                 fmt_layer.boxed()
@@ -234,7 +368,13 @@ pub fn init_logger(level: LogLevel, config: &LogConfig) -> Result<(), Error> {
                     .json()
                     .with_target(config.with_target)
                     .with_thread_names(config.with_thread_names)
-                    .with_line_number(config.with_line_number);
+                    .with_line_number(config.with_line_number)
+                    .with_writer(writer);
+                let fmt_layer = if plain {
+                    fmt_layer.with_ansi(false)
+                } else {
+                    fmt_layer
+                };
 
                 let fmt_layer = if config.with_timestamp {
                     fmt_layer.boxed()
@@ -256,12 +396,12 @@ pub fn init_logger(level: LogLevel, config: &LogConfig) -> Result<(), Error> {
         }
     }
 
-    Ok(())
+    Ok(LogGuard { _worker: worker })
 }
 
 /// Initialize logger with simple configuration (for backward compatibility)
 pub fn init_simple_logger(level: LogLevel) -> Result<(), Error> {
-    init_logger(level, &LogConfig::default())
+    init_logger(level, &LogConfig::default()).map(|_| ())
 }
 
 /// Create a span with the given name and level
@@ -282,7 +422,7 @@ pub fn create_info_span(name: &str) -> Span {
 
 /// Add fields to the current span
 pub fn add_field<T: std::fmt::Debug>(key: &str, value: T) {
-    Span::current().record(key, &tracing::field::debug(&value));
+    Span::current().record(key, tracing::field::debug(&value));
 }
 
 /// Testing utilities for applications using this logging library
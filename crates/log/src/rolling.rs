@@ -0,0 +1,104 @@
+//! Size-based rolling file writer
+//!
+//! `tracing-appender` only rotates on time boundaries, so size-based rotation
+//! is implemented here. The active file keeps its configured name and rotated
+//! files get a numeric suffix (`api.log.1`, `api.log.2`, ...), with `.1` being
+//! the most recent. As with `tracing-appender`, `max_files` counts the active file
+//! and `0` means unlimited.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Writer that rotates the log file once it grows past `max_bytes`
+pub(crate) struct SizeRollingWriter {
+    path: PathBuf,
+    file: File,
+    written: u64,
+    max_bytes: u64,
+    max_files: Option<usize>,
+}
+
+impl SizeRollingWriter {
+    /// Open (or create) `directory/file_name` in append mode
+    pub(crate) fn new(
+        directory: &Path,
+        file_name: &str,
+        max_bytes: u64,
+        max_files: Option<usize>,
+    ) -> io::Result<Self> {
+        fs::create_dir_all(directory)?;
+
+        let path = directory.join(file_name);
+        let file = open_append(&path)?;
+        let written = file.metadata()?.len();
+
+        Ok(Self {
+            path,
+            file,
+            written,
+            max_bytes,
+            max_files: max_files.filter(|&max| max > 0),
+        })
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{index}"));
+        PathBuf::from(path)
+    }
+
+    /// Shift every rotated file up by one and start a fresh active file
+    fn roll(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        // Rotated files to keep next to the active one
+        let keep = self.max_files.map(|max| max - 1);
+
+        let mut last = 0;
+        while self.rotated_path(last + 1).exists() {
+            last += 1;
+        }
+
+        // Files past the limit, including leftovers from a larger earlier setting, are removed
+        for index in (1..=last).rev() {
+            if keep.is_some_and(|keep| index >= keep) {
+                fs::remove_file(self.rotated_path(index))?;
+            } else {
+                fs::rename(self.rotated_path(index), self.rotated_path(index + 1))?;
+            }
+        }
+
+        if keep == Some(0) {
+            fs::remove_file(&self.path)?;
+        } else {
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        self.file = open_append(&self.path)?;
+        self.written = 0;
+
+        Ok(())
+    }
+}
+
+impl Write for SizeRollingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.roll()?;
+        }
+
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
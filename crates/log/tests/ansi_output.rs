@@ -0,0 +1,75 @@
+use std::process::Command;
+
+use quillai_log::{info, init_logger, FileOutput, LogConfig, LogLevel, LogOutput};
+
+// The global subscriber can only be set once per process, so each case runs the
+// `child` test below in its own process and inspects what it wrote.
+const CHILD_OUTPUT: &str = "QUILLAI_LOG_TEST_OUTPUT";
+
+#[test]
+fn child() {
+    let output = match std::env::var(CHILD_OUTPUT).as_deref() {
+        Ok("stdout") => LogOutput::Stdout,
+        Ok(directory) => LogOutput::File(FileOutput::new(directory, "test.log")),
+        Err(_) => return,
+    };
+    let config = LogConfig {
+        output,
+        ..Default::default()
+    };
+
+    let guard = init_logger(LogLevel::Info, &config).unwrap();
+    info!("Checking colour output");
+    drop(guard);
+}
+
+fn run_child(output: &str, no_color: bool) -> String {
+    let mut command = Command::new(std::env::current_exe().unwrap());
+    command
+        .args(["child", "--exact", "--nocapture"])
+        .env(CHILD_OUTPUT, output)
+        .env_remove("NO_COLOR")
+        .env_remove("RUST_LOG");
+    if no_color {
+        command.env("NO_COLOR", "1");
+    }
+
+    let output = command.output().unwrap();
+    assert!(output.status.success());
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_stdout_respects_no_color() {
+    let colored = run_child("stdout", false);
+    assert!(colored.contains("Checking colour output"));
+    assert!(colored.contains("\x1b["));
+
+    let plain = run_child("stdout", true);
+    assert!(plain.contains("Checking colour output"));
+    assert!(
+        !plain.contains("\x1b["),
+        "NO_COLOR should disable ANSI codes"
+    );
+}
+
+#[test]
+fn test_file_output_has_no_ansi() {
+    let directory = std::env::temp_dir().join(format!("quillai_log_ansi_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+
+    run_child(directory.to_str().unwrap(), false);
+
+    let contents = std::fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap())
+        .collect::<String>();
+    assert!(contents.contains("Checking colour output"));
+    assert!(
+        !contents.contains("\x1b["),
+        "file output should not contain ANSI codes"
+    );
+
+    let _ = std::fs::remove_dir_all(&directory);
+}
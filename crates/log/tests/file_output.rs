@@ -0,0 +1,47 @@
+use quillai_log::{info, init_logger, FileOutput, LogConfig, LogLevel, LogOutput, LogRotation};
+
+// Lives in its own test binary because the global subscriber can only be set once
+// per process.
+#[test]
+fn test_size_rotated_file_output() {
+    let directory = std::env::temp_dir().join(format!("quillai_log_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+
+    // Leftovers from an earlier, larger max_files setting get pruned on rotation
+    std::fs::create_dir_all(&directory).unwrap();
+    for index in 1..=4 {
+        std::fs::write(directory.join(format!("test.log.{index}")), "stale\n").unwrap();
+    }
+
+    let config = LogConfig {
+        with_timestamp: false,
+        output: LogOutput::File(FileOutput {
+            rotation: LogRotation::Size(256),
+            max_files: Some(2),
+            ..FileOutput::new(&directory, "test.log")
+        }),
+        ..Default::default()
+    };
+
+    let guard = init_logger(LogLevel::Info, &config).unwrap();
+    for i in 0..50 {
+        info!(iteration = i, "Writing enough records to force rotation");
+    }
+    // Dropping the guard flushes the non-blocking writer
+    drop(guard);
+
+    let active = std::fs::read_to_string(directory.join("test.log")).unwrap();
+    assert!(active.contains("Writing enough records"));
+    assert!(
+        !active.contains("\x1b["),
+        "file output should not contain ANSI codes"
+    );
+    // max_files counts the active file, so only one rotated file is kept
+    let rotated = std::fs::read_to_string(directory.join("test.log.1")).unwrap();
+    assert!(!rotated.contains("stale"));
+    assert!(!directory.join("test.log.2").exists());
+    assert!(!directory.join("test.log.3").exists());
+    assert!(!directory.join("test.log.4").exists());
+
+    let _ = std::fs::remove_dir_all(&directory);
+}
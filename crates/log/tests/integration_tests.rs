@@ -1,4 +1,4 @@
-use quillai_log::{LogLevel, LogConfig, LogFormat, init_logger, init_simple_logger};
+use quillai_log::{LogLevel, LogConfig, LogFormat, LogOutput, init_logger, init_simple_logger};
 use quillai_log::{info, debug, warn, error};
 use quillai_log::{info_span, create_span, Level};
 
@@ -66,6 +66,7 @@ fn test_log_config_default() {
     assert!(!config.with_thread_names);
     assert!(!config.with_line_number);
     assert!(config.env_filter.is_none());
+    assert_eq!(config.output, LogOutput::Stdout);
 }
 
 #[test]
@@ -77,6 +78,7 @@ fn test_advanced_logger_initialization() {
         with_thread_names: false,
         with_line_number: true,
         env_filter: Some("debug".to_string()),
+        output: LogOutput::Stderr,
     };
     
     // This might fail if subscriber is already initialized, which is fine
@@ -158,7 +160,7 @@ fn test_json_format_with_feature() {
     let result = init_logger(LogLevel::Off, &config);
     // Might fail if already initialized, but shouldn't fail due to missing feature
     match result {
-        Ok(()) => {
+        Ok(_guard) => {
             info!(test_field = "test_value", "JSON format test");
         }
         Err(e) => {
@@ -5,9 +5,11 @@
 //!
 //! The binary is integrated into the `cargo` command line by using an
 //! alias in `.cargo/config`.
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use duct::cmd;
 use std::error::Error;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Parser)]
 #[command(name = "xtasks")]
@@ -23,10 +25,16 @@ pub enum Commands {
     Api(ApiArgs),
     /// Run the blog.
     Blog(BlogArgs),
+    /// Build a WASM package and report its size against the budget.
+    Wasm(WasmArgs),
 
     /// Run dev.
     #[command(subcommand)]
     Dev(DevCommands),
+
+    /// Run tests.
+    #[command(subcommand)]
+    Test(TestCommands),
 }
 
 #[derive(Debug, Subcommand)]
@@ -35,6 +43,12 @@ pub enum DevCommands {
     App,
 }
 
+#[derive(Debug, Subcommand)]
+pub enum TestCommands {
+    /// Run WASM tests in a headless browser.
+    Wasm(WasmTestArgs),
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = App::parse();
 
@@ -42,9 +56,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         Some(command) => match command {
             Commands::Api(args) => api(args),
             Commands::Blog(args) => blog(args),
+            Commands::Wasm(args) => wasm(args),
             Commands::Dev(editor_cmd) => match editor_cmd {
                 DevCommands::App => app_dev(),
             },
+            Commands::Test(test_cmd) => match test_cmd {
+                TestCommands::Wasm(args) => test_wasm(args),
+            },
         },
         None => {
             println!("No command specified.");
@@ -93,6 +111,15 @@ pub struct ApiArgs {
     /// Log level
     #[clap(long, default_value = "info")]
     log_level: String,
+
+    /// JWT signing secret (the default is for local development only)
+    #[clap(
+        long,
+        env = "QUILLAI_API_JWT_SECRET",
+        default_value = "quillai-local-development-jwt-secret",
+        hide_env_values = true
+    )]
+    jwt_secret: String,
 }
 
 pub fn api(args: ApiArgs) -> Result<(), Box<dyn Error>> {
@@ -113,7 +140,10 @@ pub fn api(args: ApiArgs) -> Result<(), Box<dyn Error>> {
     ];
 
     bunt::println!("{$magenta}Running API on port {[bold]}...{/$}", port);
-    cmd("systemfd", arguments).read()?;
+    // Passed through the environment so it doesn't show up in the process list.
+    cmd("systemfd", arguments)
+        .env("QUILLAI_API_JWT_SECRET", &args.jwt_secret)
+        .read()?;
 
     Ok(())
 }
@@ -131,3 +161,299 @@ pub fn app_dev() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum WasmTarget {
+    Web,
+    Nodejs,
+    Bundler,
+}
+
+impl WasmTarget {
+    fn as_str(&self) -> &'static str {
+        match self {
+            WasmTarget::Web => "web",
+            WasmTarget::Nodejs => "nodejs",
+            WasmTarget::Bundler => "bundler",
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct WasmArgs {
+    /// Crate to build
+    #[clap(long, default_value = "crates/parchment")]
+    crate_dir: PathBuf,
+
+    /// wasm-pack target
+    #[clap(long, value_enum, default_value = "web")]
+    target: WasmTarget,
+
+    /// Size budget file
+    #[clap(long, default_value = "xtask/wasm-budget.json")]
+    budget: PathBuf,
+
+    /// Skip the wasm-opt pass wasm-pack runs on release builds
+    #[clap(long)]
+    no_opt: bool,
+}
+
+pub fn wasm(args: WasmArgs) -> Result<(), Box<dyn Error>> {
+    let crate_dir = args.crate_dir.to_string_lossy().to_string();
+
+    bunt::println!(
+        "{$magenta}Building {[bold]} for {[bold]}...{/$}",
+        crate_dir,
+        args.target.as_str()
+    );
+    // wasm-pack runs wasm-opt itself on release builds, using the level set in the
+    // crate's `[package.metadata.wasm-pack.profile.release]` section.
+    let mut arguments = vec!["build", "--release", "--target", args.target.as_str()];
+    if args.no_opt {
+        arguments.push("--no-opt");
+    }
+    arguments.push(&crate_dir);
+
+    cmd("wasm-pack", arguments).run()?;
+
+    let wasm_file = find_wasm_file(&args.crate_dir.join("pkg"))?;
+
+    let raw = std::fs::read(&wasm_file)?;
+    let sizes = [
+        ("raw", raw.len() as u64),
+        ("gzip", gzip_size(&raw)?),
+        ("brotli", brotli_size(&raw)?),
+    ];
+
+    let budget: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&args.budget)?)?;
+
+    let mut over_budget = false;
+    bunt::println!("{$bold}{:<8} {:>12} {:>12}{/$}", "", "size", "budget");
+    for (name, size) in sizes {
+        match budget.get(name).and_then(|v| v.as_u64()) {
+            Some(limit) if size > limit => {
+                over_budget = true;
+                bunt::println!(
+                    "{:<8} {$red}{:>12}{/$} {:>12}",
+                    name,
+                    format_size(size),
+                    format_size(limit)
+                );
+            }
+            Some(limit) => {
+                bunt::println!(
+                    "{:<8} {$green}{:>12}{/$} {:>12}",
+                    name,
+                    format_size(size),
+                    format_size(limit)
+                );
+            }
+            None => println!("{:<8} {:>12} {:>12}", name, format_size(size), "-"),
+        }
+    }
+
+    if over_budget {
+        bunt::eprintln!("{$red}WASM size budget exceeded.{/$}");
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+fn find_wasm_file(pkg_dir: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    for entry in std::fs::read_dir(pkg_dir)? {
+        let path = entry?.path();
+        if path.to_string_lossy().ends_with("_bg.wasm") {
+            return Ok(path);
+        }
+    }
+
+    Err(format!("no *_bg.wasm file found in {}", pkg_dir.display()).into())
+}
+
+fn gzip_size(bytes: &[u8]) -> Result<u64, Box<dyn Error>> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+    encoder.write_all(bytes)?;
+
+    Ok(encoder.finish()?.len() as u64)
+}
+
+fn brotli_size(bytes: &[u8]) -> Result<u64, Box<dyn Error>> {
+    let mut output = Vec::new();
+    {
+        let mut writer = brotli::CompressorWriter::new(&mut output, 4096, 11, 22);
+        writer.write_all(bytes)?;
+    }
+
+    Ok(output.len() as u64)
+}
+
+fn format_size(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{bytes} B")
+    } else {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum Browser {
+    Chrome,
+    Firefox,
+}
+
+impl Browser {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Browser::Chrome => "chrome",
+            Browser::Firefox => "firefox",
+        }
+    }
+
+    fn as_flag(&self) -> &'static str {
+        match self {
+            Browser::Chrome => "--chrome",
+            Browser::Firefox => "--firefox",
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct WasmTestArgs {
+    /// Only run tests whose name contains this filter
+    filter: Option<String>,
+
+    /// Crates to test
+    #[clap(long = "crate", default_values = ["crates/parchment", "crates/editor"])]
+    crates: Vec<PathBuf>,
+
+    /// Browsers to run the tests in
+    #[clap(long, value_enum, default_values = ["chrome"])]
+    browser: Vec<Browser>,
+}
+
+pub fn test_wasm(args: WasmTestArgs) -> Result<(), Box<dyn Error>> {
+    let mut results = Vec::new();
+
+    for crate_dir in &args.crates {
+        let crate_dir = crate_dir.to_string_lossy().to_string();
+
+        for browser in &args.browser {
+            bunt::println!(
+                "{$magenta}Testing {[bold]} in {[bold]}...{/$}",
+                crate_dir,
+                browser.as_str()
+            );
+
+            let mut arguments = vec!["test", "--headless", browser.as_flag(), &crate_dir];
+            if let Some(filter) = &args.filter {
+                arguments.extend(["--", filter.as_str()]);
+            }
+
+            // Stream the output as it arrives; driver downloads and slow runs can take minutes.
+            let reader = cmd("wasm-pack", arguments)
+                .stderr_to_stdout()
+                .unchecked()
+                .reader()?;
+
+            let mut output = String::new();
+            for line in BufReader::new(&reader).lines() {
+                let line = line?;
+                println!("{line}");
+                output.push_str(&line);
+                output.push('\n');
+            }
+
+            let success = reader
+                .try_wait()?
+                .is_some_and(|output| output.status.success());
+            let (passed, failed) = count_test_results(&output);
+            results.push((crate_dir.clone(), *browser, success, passed, failed));
+        }
+    }
+
+    let mut success = true;
+    bunt::println!(
+        "\n{$bold}{:<24} {:<8} {:>7} {:>7}{/$}",
+        "crate",
+        "browser",
+        "passed",
+        "failed"
+    );
+    for (crate_dir, browser, ok, passed, failed) in results {
+        let browser = browser.as_str();
+        if ok {
+            bunt::println!(
+                "{:<24} {:<8} {$green}{:>7}{/$} {:>7}",
+                crate_dir,
+                browser,
+                passed,
+                failed
+            );
+        } else {
+            success = false;
+            bunt::println!(
+                "{:<24} {:<8} {:>7} {$red}{:>7}{/$}",
+                crate_dir,
+                browser,
+                passed,
+                failed
+            );
+        }
+    }
+
+    if !success {
+        bunt::eprintln!("{$red}WASM tests failed.{/$}");
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Sum the passed/failed counts of every `test result:` line in the output.
+fn count_test_results(output: &str) -> (u64, u64) {
+    let mut passed = 0;
+    let mut failed = 0;
+
+    for line in output.lines().filter(|l| l.contains("test result:")) {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        for pair in words.windows(2) {
+            let count = pair[0].parse::<u64>().unwrap_or(0);
+            match pair[1].trim_end_matches([';', ',']) {
+                "passed" => passed += count,
+                "failed" => failed += count,
+                _ => {}
+            }
+        }
+    }
+
+    (passed, failed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_test_results_sums_every_summary_line() {
+        let output = "\
+running 3 tests
+test tests::insert ... ok
+test result: ok. 3 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.05s
+
+running 2 tests
+test tests::mutation ... FAILED
+test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 filtered out; finished in 1.20s
+";
+
+        assert_eq!(count_test_results(output), (4, 1));
+    }
+
+    #[test]
+    fn count_test_results_without_summary() {
+        assert_eq!(
+            count_test_results("Error: failed to download chromedriver\n"),
+            (0, 0)
+        );
+    }
+}